@@ -9,11 +9,24 @@ fn rep_link_rx_up(linkno: u8) -> bool {
     }
 }
 
+// Ping retry backoff: the interval doubles on each retry up to a cap, and
+// a per-repeater offset (derived from repno) is added so that repeaters
+// coming up at the same time do not retry in lockstep.
+#[cfg(has_drtio_routing)]
+const PING_INTERVAL_BASE_MS: u64 = 100;
+#[cfg(has_drtio_routing)]
+const PING_INTERVAL_CAP_MS: u64 = 2000;
+#[cfg(has_drtio_routing)]
+const PING_MAX_RETRIES: u16 = 200;
+
 #[derive(Clone, Copy, PartialEq)]
 enum RepeaterState {
     Down,
     SendPing { ping_count: u16 },
     WaitPingReply { ping_count: u16, timeout: u64 },
+    SyncTSC { timeout: u64, finish_bringup: bool },
+    LoadRoutingTable { destination: usize, timeout: u64, finish_bringup: bool },
+    SetRank { timeout: u64 },
     Up,
     Failed
 }
@@ -22,11 +35,53 @@ impl Default for RepeaterState {
     fn default() -> RepeaterState { RepeaterState::Down }
 }
 
+/// Snapshot of a repeater's link health, for satellite management/CLI
+/// reporting. Counters saturate rather than wrap.
 #[derive(Clone, Copy, Default)]
+pub struct RepeaterCounters {
+    pub ping_attempts: u32,
+    pub bringups: u32,
+    pub rx_down_events: u32,
+    pub aux_errors: u32,
+    pub tsc_sync_failures: u32,
+}
+
+#[derive(Clone, Copy)]
 pub struct Repeater {
     repno: u8,
     auxno: u8,
-    state: RepeaterState
+    state: RepeaterState,
+    counters: RepeaterCounters,
+    // Hops last successfully pushed to the remote repeater for each
+    // destination, so that routing updates while the link is Up only
+    // resend the paths that actually changed.
+    #[cfg(has_drtio_routing)]
+    cached_routing_table: [[u8; drtio_routing::MAX_HOPS]; drtio_routing::DEST_COUNT]
+}
+
+#[cfg(has_drtio_routing)]
+impl Default for Repeater {
+    fn default() -> Repeater {
+        Repeater {
+            repno: 0,
+            auxno: 0,
+            state: RepeaterState::default(),
+            counters: RepeaterCounters::default(),
+            cached_routing_table: [[0; drtio_routing::MAX_HOPS]; drtio_routing::DEST_COUNT]
+        }
+    }
+}
+
+#[cfg(not(has_drtio_routing))]
+impl Default for Repeater {
+    fn default() -> Repeater {
+        Repeater {
+            repno: 0,
+            auxno: 0,
+            state: RepeaterState::default(),
+            counters: RepeaterCounters::default()
+        }
+    }
 }
 
 #[cfg(has_drtio_routing)]
@@ -35,10 +90,15 @@ impl Repeater {
         Repeater {
             repno: repno,
             auxno: repno + 1,
-            state: RepeaterState::Down
+            ..Default::default()
         }
     }
 
+    /// Returns a snapshot of this repeater's link health counters.
+    pub fn counters(&self) -> RepeaterCounters {
+        self.counters
+    }
+
     pub fn service(&mut self, routing_table: &drtio_routing::RoutingTable, rank: u8) {
         match self.state {
             RepeaterState::Down => {
@@ -50,38 +110,25 @@ impl Repeater {
             RepeaterState::SendPing { ping_count } => {
                 if rep_link_rx_up(self.repno) {
                     drtioaux::send_link(self.auxno, &drtioaux::Packet::EchoRequest).unwrap();
+                    self.counters.ping_attempts = self.counters.ping_attempts.saturating_add(1);
                     self.state = RepeaterState::WaitPingReply {
                         ping_count: ping_count + 1,
-                        timeout: clock::get_ms() + 100
+                        timeout: clock::get_ms() + self.ping_timeout_ms(ping_count)
                     }
                 } else {
                     error!("[REP#{}] link RX went down during ping", self.repno);
-                    self.state = RepeaterState::Down;
+                    self.counters.rx_down_events = self.counters.rx_down_events.saturating_add(1);
+                    self.go_down();
                 }
             }
             RepeaterState::WaitPingReply { ping_count, timeout } => {
                 if rep_link_rx_up(self.repno) {
                     if let Ok(Some(drtioaux::Packet::EchoReply)) = drtioaux::recv_link(self.auxno) {
                         info!("[REP#{}] remote replied after {} packets", self.repno, ping_count);
-                        self.state = RepeaterState::Up;
-                        if let Err(e) = self.sync_tsc() {
-                            error!("[REP#{}] failed to sync TSC ({})", self.repno, e);
-                            self.state = RepeaterState::Failed;
-                            return;
-                        }
-                        if let Err(e) = self.load_routing_table(routing_table) {
-                            error!("[REP#{}] failed to sync TSC ({})", self.repno, e);
-                            self.state = RepeaterState::Failed;
-                            return;
-                        }
-                        if let Err(e) = self.set_rank(rank) {
-                            error!("[REP#{}] failed to sync TSC ({})", self.repno, e);
-                            self.state = RepeaterState::Failed;
-                            return;
-                        }
+                        self.begin_sync_tsc(true);
                     } else {
                         if clock::get_ms() > timeout {
-                            if ping_count > 200 {
+                            if ping_count > PING_MAX_RETRIES {
                                 error!("[REP#{}] ping failed", self.repno);
                                 self.state = RepeaterState::Failed;
                             } else {
@@ -91,97 +138,193 @@ impl Repeater {
                     }
                 } else {
                     error!("[REP#{}] link RX went down during ping", self.repno);
-                    self.state = RepeaterState::Down;
+                    self.counters.rx_down_events = self.counters.rx_down_events.saturating_add(1);
+                    self.go_down();
+                }
+            }
+            RepeaterState::SyncTSC { timeout, finish_bringup } => {
+                if !rep_link_rx_up(self.repno) {
+                    error!("[REP#{}] link RX went down during TSC sync", self.repno);
+                    self.counters.rx_down_events = self.counters.rx_down_events.saturating_add(1);
+                    self.go_down();
+                    return;
+                }
+                // TSCAck is the only aux packet that is sent spontaneously
+                // by the satellite, in response to a TSC set on the RT link.
+                match drtioaux::recv_link(self.auxno) {
+                    Ok(Some(drtioaux::Packet::TSCAck)) => {
+                        if finish_bringup {
+                            if !self.begin_load_routing_table(routing_table, 0, true) {
+                                self.begin_set_rank(rank);
+                            }
+                        } else {
+                            info!("[REP#{}] TSC resynced", self.repno);
+                            self.state = RepeaterState::Up;
+                        }
+                    }
+                    Ok(None) => {
+                        if clock::get_ms() > timeout {
+                            error!("[REP#{}] failed to sync TSC (timeout)", self.repno);
+                            self.counters.tsc_sync_failures = self.counters.tsc_sync_failures.saturating_add(1);
+                            self.state = RepeaterState::Failed;
+                        }
+                    }
+                    _ => {
+                        error!("[REP#{}] failed to sync TSC (unexpected reply)", self.repno);
+                        self.counters.tsc_sync_failures = self.counters.tsc_sync_failures.saturating_add(1);
+                        self.state = RepeaterState::Failed;
+                    }
+                }
+            }
+            RepeaterState::LoadRoutingTable { destination, timeout, finish_bringup } => {
+                if !rep_link_rx_up(self.repno) {
+                    error!("[REP#{}] link RX went down while loading routing table", self.repno);
+                    self.counters.rx_down_events = self.counters.rx_down_events.saturating_add(1);
+                    self.go_down();
+                    return;
+                }
+                match drtioaux::recv_link(self.auxno) {
+                    Ok(Some(drtioaux::Packet::RoutingAck)) => {
+                        if !self.begin_load_routing_table(routing_table, destination + 1, finish_bringup) {
+                            if finish_bringup {
+                                self.begin_set_rank(rank);
+                            } else {
+                                info!("[REP#{}] routing table update applied", self.repno);
+                                self.state = RepeaterState::Up;
+                            }
+                        }
+                    }
+                    Ok(None) => {
+                        if clock::get_ms() > timeout {
+                            error!("[REP#{}] failed to load routing table (timeout)", self.repno);
+                            self.counters.aux_errors = self.counters.aux_errors.saturating_add(1);
+                            self.state = RepeaterState::Failed;
+                        }
+                    }
+                    _ => {
+                        error!("[REP#{}] failed to load routing table (unexpected reply)", self.repno);
+                        self.counters.aux_errors = self.counters.aux_errors.saturating_add(1);
+                        self.state = RepeaterState::Failed;
+                    }
+                }
+            }
+            RepeaterState::SetRank { timeout } => {
+                if !rep_link_rx_up(self.repno) {
+                    error!("[REP#{}] link RX went down while setting rank", self.repno);
+                    self.counters.rx_down_events = self.counters.rx_down_events.saturating_add(1);
+                    self.go_down();
+                    return;
+                }
+                match drtioaux::recv_link(self.auxno) {
+                    Ok(Some(drtioaux::Packet::RoutingAck)) => {
+                        info!("[REP#{}] link initialization completed", self.repno);
+                        self.counters.bringups = self.counters.bringups.saturating_add(1);
+                        self.state = RepeaterState::Up;
+                    }
+                    Ok(None) => {
+                        if clock::get_ms() > timeout {
+                            error!("[REP#{}] failed to set rank (timeout)", self.repno);
+                            self.counters.aux_errors = self.counters.aux_errors.saturating_add(1);
+                            self.state = RepeaterState::Failed;
+                        }
+                    }
+                    _ => {
+                        error!("[REP#{}] failed to set rank (unexpected reply)", self.repno);
+                        self.counters.aux_errors = self.counters.aux_errors.saturating_add(1);
+                        self.state = RepeaterState::Failed;
+                    }
                 }
             }
             RepeaterState::Up => {
                 if !rep_link_rx_up(self.repno) {
                     info!("[REP#{}] link is down", self.repno);
-                    self.state = RepeaterState::Down;
+                    self.counters.rx_down_events = self.counters.rx_down_events.saturating_add(1);
+                    self.go_down();
                 }
             }
             RepeaterState::Failed => {
                 if !rep_link_rx_up(self.repno) {
                     info!("[REP#{}] link is down", self.repno);
-                    self.state = RepeaterState::Down;
+                    self.counters.rx_down_events = self.counters.rx_down_events.saturating_add(1);
+                    self.go_down();
                 }
             }
         }
     }
 
-    fn recv_aux_timeout(&self, timeout: u32) -> Result<drtioaux::Packet, &'static str> {
-        let max_time = clock::get_ms() + timeout as u64;
-        loop {
-            if !rep_link_rx_up(self.repno) {
-                return Err("link went down");
-            }
-            if clock::get_ms() > max_time {
-                return Err("timeout");
-            }
-            match drtioaux::recv_link(self.auxno) {
-                Ok(Some(packet)) => return Ok(packet),
-                Ok(None) => (),
-                Err(_) => return Err("aux packet error")
-            }
-        }
+    // Geometric backoff capped at PING_INTERVAL_CAP_MS, plus a small
+    // deterministic per-repeater offset to de-correlate retries across
+    // links that came up at the same time.
+    fn ping_timeout_ms(&self, ping_count: u16) -> u64 {
+        let backoff = PING_INTERVAL_BASE_MS.saturating_mul(1u64 << ping_count.min(16));
+        let interval = backoff.min(PING_INTERVAL_CAP_MS);
+        let jitter = (self.repno as u64 * 17) % (PING_INTERVAL_BASE_MS / 2);
+        interval + jitter
     }
 
-    pub fn sync_tsc(&self) -> Result<(), &'static str> {
-        if self.state != RepeaterState::Up {
-            return Ok(());
-        }
-
+    // Fires off the TSC set and moves to SyncTSC to await the TSCAck.
+    fn begin_sync_tsc(&mut self, finish_bringup: bool) {
         let repno = self.repno as usize;
         unsafe {
             (csr::DRTIOREP[repno].set_time_write)(1);
             while (csr::DRTIOREP[repno].set_time_read)() == 1 {}
         }
-
-        // TSCAck is the only aux packet that is sent spontaneously
-        // by the satellite, in response to a TSC set on the RT link.
-        let reply = self.recv_aux_timeout(10000)?;
-        if reply == drtioaux::Packet::TSCAck {
-            return Ok(());
-        } else {
-            return Err("unexpected reply");
-        }
+        self.state = RepeaterState::SyncTSC {
+            timeout: clock::get_ms() + 10000,
+            finish_bringup: finish_bringup
+        };
     }
 
-    pub fn set_path(&self, destination: u8, hops: &[u8; drtio_routing::MAX_HOPS]) -> Result<(), &'static str> {
+    /// Resynchronizes the TSC without a link cycle; no-op unless Up.
+    pub fn sync_tsc(&mut self) {
         if self.state != RepeaterState::Up {
-            return Ok(());
+            return;
         }
-
-        drtioaux::send_link(self.auxno, &drtioaux::Packet::RoutingSetPath {
-            destination: destination,
-            hops: *hops
-        }).unwrap();
-        let reply = self.recv_aux_timeout(200)?;
-        if reply != drtioaux::Packet::RoutingAck {
-            return Err("unexpected reply");
-        }
-        Ok(())
+        self.begin_sync_tsc(false);
     }
 
-    pub fn load_routing_table(&self, routing_table: &drtio_routing::RoutingTable) -> Result<(), &'static str> {
-        for i in 0..drtio_routing::DEST_COUNT {
-            self.set_path(i as u8, &routing_table.0[i])?;
+    // Sends RoutingSetPath for the first destination at/after `destination` that
+    // needs it (all of them if finish_bringup); returns false if none did.
+    fn begin_load_routing_table(&mut self, routing_table: &drtio_routing::RoutingTable,
+                                 destination: usize, finish_bringup: bool) -> bool {
+        for dest in destination..drtio_routing::DEST_COUNT {
+            let hops = routing_table.0[dest];
+            if finish_bringup || hops != self.cached_routing_table[dest] {
+                drtioaux::send_link(self.auxno, &drtioaux::Packet::RoutingSetPath {
+                    destination: dest as u8,
+                    hops: hops
+                }).unwrap();
+                self.cached_routing_table[dest] = hops;
+                self.state = RepeaterState::LoadRoutingTable {
+                    destination: dest,
+                    timeout: clock::get_ms() + 200,
+                    finish_bringup: finish_bringup
+                };
+                return true;
+            }
         }
-        Ok(())
+        false
     }
 
-    pub fn set_rank(&self, rank: u8) -> Result<(), &'static str> {
+    /// Pushes only the routing table changes for this repeater; no-op unless Up.
+    pub fn sync_routing_table(&mut self, routing_table: &drtio_routing::RoutingTable) {
         if self.state != RepeaterState::Up {
-            return Ok(());
+            return;
         }
+        self.begin_load_routing_table(routing_table, 0, false);
+    }
+
+    // The remote forgets its routing table once the link drops, so drop our cache too.
+    fn go_down(&mut self) {
+        self.cached_routing_table = [[0; drtio_routing::MAX_HOPS]; drtio_routing::DEST_COUNT];
+        self.state = RepeaterState::Down;
+    }
+
+    fn begin_set_rank(&mut self, rank: u8) {
         drtioaux::send_link(self.auxno, &drtioaux::Packet::RoutingSetRank {
             rank: rank
         }).unwrap();
-        let reply = self.recv_aux_timeout(200)?;
-        if reply != drtioaux::Packet::RoutingAck {
-            return Err("unexpected reply");
-        }
-        Ok(())
+        self.state = RepeaterState::SetRank { timeout: clock::get_ms() + 200 };
     }
 }
 
@@ -191,5 +334,9 @@ impl Repeater {
 
     pub fn service(&self) { }
 
-    pub fn sync_tsc(&self) -> Result<(), &'static str> { Ok(()) }
-}
\ No newline at end of file
+    pub fn counters(&self) -> RepeaterCounters { RepeaterCounters::default() }
+
+    pub fn sync_tsc(&mut self) { }
+
+    pub fn sync_routing_table(&mut self, _routing_table: &drtio_routing::RoutingTable) { }
+}